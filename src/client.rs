@@ -0,0 +1,306 @@
+//! An authenticated HTTP client for the [Game Client API](https://developer.riotgames.com/docs/lol#game-client-api),
+//! built on top of a [`RiotLockFile`](crate::RiotLockFile). Consumers no longer have to hand-roll
+//! a `reqwest` client, disable certificate verification and base64 the auth themselves.
+//!
+//! The default transport is `reqwest`, gated behind the `reqwest-client` feature (enabled by
+//! default). Plugging in a different HTTP stack only requires implementing [`Client`] and
+//! [`Response`].
+
+use crate::{JsonParse, Result, RiotLockFile};
+#[cfg(feature = "reqwest-client")]
+use crate::LeagueConnectorError;
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use snafu::ResultExt;
+use std::future::Future;
+use std::pin::Pin;
+
+/// A boxed, `Send` future, matching the style of other backend-agnostic async traits.
+pub type BoxFut<'a, T> = Pin<Box<dyn Future<Output = T> + Send + 'a>>;
+
+/// The HTTP method for an [`LcuClient`] request.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Method {
+    Get,
+    Post,
+    Put,
+    Patch,
+    Delete,
+}
+
+/// A backend-agnostic HTTP transport. Implement this to plug a different HTTP stack (e.g.
+/// `hyper` or `surf`) into [`LcuClient`] instead of the default `reqwest` backend.
+pub trait Client {
+    type Resp: Response;
+
+    fn request(
+        &self,
+        method: Method,
+        url: String,
+        auth_header: String,
+        body: Option<String>,
+    ) -> BoxFut<'_, Result<Self::Resp>>;
+}
+
+/// The raw response returned by a [`Client`] implementation.
+pub trait Response {
+    fn body(self) -> BoxFut<'static, Result<String>>;
+}
+
+/// Authenticated LCU HTTP client. Generic over the [`Client`] backend; with the
+/// `reqwest-client` feature enabled, [`LcuClient::new`] builds one backed by [`ReqwestClient`].
+pub struct LcuClient<C: Client> {
+    lockfile: RiotLockFile,
+    client: C,
+}
+
+#[cfg(feature = "reqwest-client")]
+impl LcuClient<ReqwestClient> {
+    /// Builds an `LcuClient` backed by the default `reqwest` transport.
+    pub fn new(lockfile: RiotLockFile) -> Self {
+        Self::with_client(lockfile, ReqwestClient::new())
+    }
+}
+
+impl<C: Client> LcuClient<C> {
+    /// Builds an `LcuClient` backed by a custom [`Client`] implementation.
+    pub fn with_client(lockfile: RiotLockFile, client: C) -> Self {
+        Self { lockfile, client }
+    }
+
+    pub async fn get<T: DeserializeOwned>(&self, endpoint: &str) -> Result<T> {
+        self.request::<(), T>(Method::Get, endpoint, None).await
+    }
+
+    pub async fn post<B: Serialize, T: DeserializeOwned>(
+        &self,
+        endpoint: &str,
+        body: &B,
+    ) -> Result<T> {
+        self.request(Method::Post, endpoint, Some(body)).await
+    }
+
+    pub async fn put<B: Serialize, T: DeserializeOwned>(
+        &self,
+        endpoint: &str,
+        body: &B,
+    ) -> Result<T> {
+        self.request(Method::Put, endpoint, Some(body)).await
+    }
+
+    pub async fn patch<B: Serialize, T: DeserializeOwned>(
+        &self,
+        endpoint: &str,
+        body: &B,
+    ) -> Result<T> {
+        self.request(Method::Patch, endpoint, Some(body)).await
+    }
+
+    pub async fn delete<T: DeserializeOwned>(&self, endpoint: &str) -> Result<T> {
+        self.request::<(), T>(Method::Delete, endpoint, None).await
+    }
+
+    async fn request<B: Serialize, T: DeserializeOwned>(
+        &self,
+        method: Method,
+        endpoint: &str,
+        body: Option<&B>,
+    ) -> Result<T> {
+        let url = format!(
+            "{}://{}:{}{}",
+            self.lockfile.protocol, self.lockfile.address, self.lockfile.port, endpoint
+        );
+        let auth_header = format!("Basic {}", self.lockfile.b64_auth);
+
+        let body = body
+            .map(serde_json::to_string)
+            .transpose()
+            .context(JsonParse)?;
+
+        let response = self.client.request(method, url, auth_header, body).await?;
+        let body = response.body().await?;
+
+        serde_json::from_str(&body).context(JsonParse)
+    }
+}
+
+/// The default [`Client`] implementation, backed by `reqwest` and accepting Riot's self-signed
+/// certificate.
+#[cfg(feature = "reqwest-client")]
+pub struct ReqwestClient {
+    inner: reqwest::Client,
+}
+
+#[cfg(feature = "reqwest-client")]
+impl ReqwestClient {
+    pub fn new() -> Self {
+        let inner = reqwest::Client::builder()
+            .danger_accept_invalid_certs(true)
+            .build()
+            .expect("failed to build the reqwest client");
+
+        Self { inner }
+    }
+}
+
+#[cfg(feature = "reqwest-client")]
+impl Default for ReqwestClient {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(feature = "reqwest-client")]
+impl Client for ReqwestClient {
+    type Resp = reqwest::Response;
+
+    fn request(
+        &self,
+        method: Method,
+        url: String,
+        auth_header: String,
+        body: Option<String>,
+    ) -> BoxFut<'_, Result<Self::Resp>> {
+        Box::pin(async move {
+            let method = match method {
+                Method::Get => reqwest::Method::GET,
+                Method::Post => reqwest::Method::POST,
+                Method::Put => reqwest::Method::PUT,
+                Method::Patch => reqwest::Method::PATCH,
+                Method::Delete => reqwest::Method::DELETE,
+            };
+
+            let mut request = self
+                .inner
+                .request(method, &url)
+                .header("Authorization", auth_header);
+
+            if let Some(body) = body {
+                request = request.header("Content-Type", "application/json").body(body);
+            }
+
+            request
+                .send()
+                .await
+                .map_err(|source| LeagueConnectorError::HttpRequest {
+                    message: source.to_string(),
+                })
+        })
+    }
+}
+
+#[cfg(feature = "reqwest-client")]
+impl Response for reqwest::Response {
+    fn body(self) -> BoxFut<'static, Result<String>> {
+        Box::pin(async move {
+            self.text()
+                .await
+                .map_err(|source| LeagueConnectorError::HttpRequest {
+                    message: source.to_string(),
+                })
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::{Arc, Mutex};
+
+    struct FakeResponse(String);
+
+    impl Response for FakeResponse {
+        fn body(self) -> BoxFut<'static, Result<String>> {
+            Box::pin(async move { Ok(self.0) })
+        }
+    }
+
+    type RecordedCall = (Method, String, String, Option<String>);
+
+    struct FakeClient {
+        calls: Arc<Mutex<Vec<RecordedCall>>>,
+        response: String,
+    }
+
+    impl Client for FakeClient {
+        type Resp = FakeResponse;
+
+        fn request(
+            &self,
+            method: Method,
+            url: String,
+            auth_header: String,
+            body: Option<String>,
+        ) -> BoxFut<'_, Result<Self::Resp>> {
+            self.calls
+                .lock()
+                .unwrap()
+                .push((method, url, auth_header, body));
+
+            let response = self.response.clone();
+            Box::pin(async move { Ok(FakeResponse(response)) })
+        }
+    }
+
+    fn lockfile() -> RiotLockFile {
+        RiotLockFile {
+            process: "LeagueClientUx".to_string(),
+            pid: 1234,
+            port: 54321,
+            password: "some_password".to_string(),
+            protocol: "https".to_string(),
+            username: "riot".to_string(),
+            address: "127.0.0.1".to_string(),
+            b64_auth: "cmlvdDpzb21lX3Bhc3N3b3Jk".to_string(),
+        }
+    }
+
+    #[test]
+    fn get_builds_url_and_auth_header() {
+        let calls = Arc::new(Mutex::new(Vec::new()));
+        let client = FakeClient {
+            calls: Arc::clone(&calls),
+            response: "\"ok\"".to_string(),
+        };
+        let lcu = LcuClient::with_client(lockfile(), client);
+
+        let result: String =
+            futures::executor::block_on(lcu.get("/lol-summoner/v1/current-summoner")).unwrap();
+
+        assert_eq!(result, "ok");
+
+        let calls = calls.lock().unwrap();
+        assert_eq!(calls.len(), 1);
+        let (method, url, auth_header, body) = &calls[0];
+        assert_eq!(*method, Method::Get);
+        assert_eq!(
+            url,
+            "https://127.0.0.1:54321/lol-summoner/v1/current-summoner"
+        );
+        assert_eq!(auth_header, "Basic cmlvdDpzb21lX3Bhc3N3b3Jk");
+        assert!(body.is_none());
+    }
+
+    #[test]
+    fn post_serializes_body_as_json() {
+        #[derive(Serialize)]
+        struct Body {
+            value: u32,
+        }
+
+        let calls = Arc::new(Mutex::new(Vec::new()));
+        let client = FakeClient {
+            calls: Arc::clone(&calls),
+            response: "{}".to_string(),
+        };
+        let lcu = LcuClient::with_client(lockfile(), client);
+
+        let _: serde_json::Value =
+            futures::executor::block_on(lcu.post("/endpoint", &Body { value: 7 })).unwrap();
+
+        let calls = calls.lock().unwrap();
+        assert_eq!(calls[0].0, Method::Post);
+        assert_eq!(calls[0].3.as_deref(), Some(r#"{"value":7}"#));
+    }
+}
+
@@ -0,0 +1,194 @@
+//! LCU event subscriptions over its WAMP-over-WebSocket endpoint, gated behind the `ws-events`
+//! feature. The LCU exposes the same state as the REST API on a `wss://127.0.0.1:{port}/`
+//! endpoint using the same credentials, which is how tools watch for gameflow changes (e.g.
+//! ready-check popups) without polling. As with `ReqwestClient`, the endpoint serves a
+//! self-signed certificate, so `LcuEventStream::connect` skips verification the same way.
+
+use crate::{LeagueConnectorError, Result, RiotLockFile};
+use futures_util::{SinkExt, Stream};
+use pin_project_lite::pin_project;
+use serde::Deserialize;
+use serde_json::Value;
+use snafu::ResultExt;
+use std::pin::Pin;
+use std::task::{Context, Poll};
+use tokio_tungstenite::tungstenite::client::IntoClientRequest;
+use tokio_tungstenite::tungstenite::http::HeaderValue;
+use tokio_tungstenite::tungstenite::Message;
+use tokio_tungstenite::{connect_async_tls_with_config, Connector, MaybeTlsStream, WebSocketStream};
+
+/// The WAMP message type used by the LCU to push API events.
+const ON_JSON_API_EVENT: &str = "OnJsonApiEvent";
+
+/// A typed LCU event, parsed from an `OnJsonApiEvent` WAMP message.
+#[derive(Debug, Clone, Deserialize, PartialEq)]
+pub struct LcuEvent {
+    pub uri: String,
+    #[serde(rename = "eventType")]
+    pub event_type: String,
+    pub data: Value,
+}
+
+pin_project! {
+    /// A live subscription to LCU events, opened against a [`RiotLockFile`].
+    ///
+    /// Implements [`futures_util::Stream`], so events can be consumed with `while let Some(event)
+    /// = stream.next().await`.
+    pub struct LcuEventStream {
+        #[pin]
+        socket: WebSocketStream<MaybeTlsStream<tokio::net::TcpStream>>,
+    }
+}
+
+impl LcuEventStream {
+    /// Connects to the LCU's event endpoint and subscribes to every `OnJsonApiEvent`.
+    pub async fn connect(lockfile: &RiotLockFile) -> Result<Self> {
+        let url = format!("wss://{}:{}/", lockfile.address, lockfile.port);
+
+        let mut request = url.into_client_request().map_err(ws_error)?;
+
+        let auth_header = format!("Basic {}", lockfile.b64_auth);
+        request
+            .headers_mut()
+            .insert("Authorization", HeaderValue::from_str(&auth_header).map_err(|source| {
+                LeagueConnectorError::WebSocket {
+                    message: source.to_string(),
+                }
+            })?);
+
+        // The LCU serves a self-signed certificate, so we need a connector that skips
+        // verification, the same way `ReqwestClient` does via `danger_accept_invalid_certs`.
+        let connector = native_tls::TlsConnector::builder()
+            .danger_accept_invalid_certs(true)
+            .build()
+            .map_err(|source| LeagueConnectorError::WebSocket {
+                message: source.to_string(),
+            })?;
+
+        let (socket, _) =
+            connect_async_tls_with_config(request, None, false, Some(Connector::NativeTls(connector)))
+                .await
+                .map_err(ws_error)?;
+
+        let mut stream = Self { socket };
+        stream.send_frame(5, ON_JSON_API_EVENT).await?;
+
+        Ok(stream)
+    }
+
+    /// Subscribes to the topic of a specific LCU endpoint, e.g.
+    /// `/lol-matchmaking/v1/ready-check`. The subscription to every `OnJsonApiEvent` made by
+    /// [`connect`](Self::connect) stays in place, so events from other endpoints keep arriving
+    /// on the stream; events are not filtered by endpoint.
+    pub async fn subscribe(&mut self, uri: &str) -> Result<()> {
+        self.send_frame(5, &topic_for(uri)).await
+    }
+
+    /// Unsubscribes from a previously [`subscribe`](Self::subscribe)d endpoint.
+    pub async fn unsubscribe(&mut self, uri: &str) -> Result<()> {
+        self.send_frame(6, &topic_for(uri)).await
+    }
+
+    async fn send_frame(&mut self, code: u8, topic: &str) -> Result<()> {
+        let frame = serde_json::json!([code, topic]).to_string();
+
+        self.socket
+            .send(Message::Text(frame))
+            .await
+            .map_err(ws_error)
+    }
+}
+
+impl futures_util::Stream for LcuEventStream {
+    type Item = Result<LcuEvent>;
+
+    fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        let mut this = self.project();
+
+        loop {
+            return match this.socket.as_mut().poll_next(cx) {
+                Poll::Ready(Some(Ok(message))) => match parse_event(message) {
+                    Some(event) => Poll::Ready(Some(event)),
+                    None => continue,
+                },
+                Poll::Ready(Some(Err(source))) => Poll::Ready(Some(Err(ws_error(source)))),
+                Poll::Ready(None) => Poll::Ready(None),
+                Poll::Pending => Poll::Pending,
+            };
+        }
+    }
+}
+
+/// The LCU subscribes to a specific endpoint by appending its path (with `/` turned into `_`)
+/// to the `OnJsonApiEvent` topic.
+fn topic_for(uri: &str) -> String {
+    format!("{}{}", ON_JSON_API_EVENT, uri.replace('/', "_"))
+}
+
+/// Parses an `[8, "OnJsonApiEvent...", {...}]` WAMP message into an [`LcuEvent`]. Any other
+/// frame (subscription acks, pings, ...) is ignored by returning `None`.
+fn parse_event(message: Message) -> Option<Result<LcuEvent>> {
+    let text = match message {
+        Message::Text(text) => text,
+        _ => return None,
+    };
+
+    let frame: Value = serde_json::from_str(&text).ok()?;
+    let frame = frame.as_array()?;
+
+    if frame.len() != 3 || frame[0].as_u64() != Some(8) {
+        return None;
+    }
+
+    Some(serde_json::from_value(frame[2].clone()).context(crate::JsonParse))
+}
+
+fn ws_error(source: tokio_tungstenite::tungstenite::Error) -> LeagueConnectorError {
+    LeagueConnectorError::WebSocket {
+        message: source.to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn topic_for_replaces_slashes() {
+        assert_eq!(
+            topic_for("/lol-matchmaking/v1/ready-check"),
+            "OnJsonApiEvent_lol-matchmaking_v1_ready-check"
+        );
+    }
+
+    #[test]
+    fn parse_event_reads_on_json_api_event_frame() {
+        let message = Message::Text(
+            serde_json::json!([
+                8,
+                "OnJsonApiEvent",
+                {
+                    "uri": "/lol-matchmaking/v1/ready-check",
+                    "eventType": "Update",
+                    "data": {"state": "InProgress"},
+                }
+            ])
+            .to_string(),
+        );
+
+        let event = parse_event(message).unwrap().unwrap();
+
+        assert_eq!(event.uri, "/lol-matchmaking/v1/ready-check");
+        assert_eq!(event.event_type, "Update");
+        assert_eq!(event.data["state"], "InProgress");
+    }
+
+    #[test]
+    fn parse_event_ignores_non_event_frames() {
+        let ack = Message::Text(serde_json::json!([5, "OnJsonApiEvent"]).to_string());
+        assert!(parse_event(ack).is_none());
+
+        let ping = Message::Ping(Vec::new());
+        assert!(parse_event(ping).is_none());
+    }
+}
@@ -1,25 +1,50 @@
 //! # league_client_connector
 //!
-//! Rust implementation for [lcu-connector](https://github.com/Pupix/lcu-connector) minus the
-//! file watching mechanism. This crate needs the League Client to be opened, in order to get the
-//! installation path for League of Legends so the `lockfile` can be retrieved correctly.
+//! Rust implementation for [lcu-connector](https://github.com/Pupix/lcu-connector). This crate
+//! needs the League Client to be opened, in order to get the installation path for League of
+//! Legends so the `lockfile` can be retrieved correctly.
 //!
-//! Note that every time the League Client is opened, it creates a new `lockfile` so a watcher or
-//! some refresh mechanism needs to be implemented to use correctly in an application.
+//! Note that every time the League Client is opened, it creates a new `lockfile`. The `watch`
+//! module (behind the `watch` feature) provides a [`watch::LockfileWatcher`] that transparently
+//! re-parses the `lockfile` whenever the client restarts.
 //!
 //! The contents of the `lockfile` are parsed and presented in a readable format so a connection to
 //! the [Game Client API](https://developer.riotgames.com/docs/lol#game-client-api) can be
 //! established.
 
 use base64::encode;
+use once_cell::sync::Lazy;
 use regex::Regex;
 use serde::{Deserialize, Serialize};
 use snafu::{ResultExt, Snafu};
 use std::env::consts::OS;
 use std::fs;
+use std::io::{self, BufRead, BufReader, Read};
 use std::path::PathBuf;
 use std::process::{Command, Stdio};
 
+static INSTALL_DIRECTORY_PATTERN: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(r"--install-directory=(?P<dir>[[:alnum:][:space:]:\-_\./\\]+)")
+        .expect("install-directory pattern is valid")
+});
+
+static APP_PORT_PATTERN: Lazy<Regex> =
+    Lazy::new(|| Regex::new(r"--app-port=(?P<value>\d+)").expect("app-port pattern is valid"));
+
+static APP_PID_PATTERN: Lazy<Regex> =
+    Lazy::new(|| Regex::new(r"--app-pid=(?P<value>\d+)").expect("app-pid pattern is valid"));
+
+static AUTH_TOKEN_PATTERN: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(r"--remoting-auth-token=(?P<value>[^\s]+)")
+        .expect("remoting-auth-token pattern is valid")
+});
+
+pub mod client;
+#[cfg(feature = "ws-events")]
+pub mod events;
+#[cfg(feature = "watch")]
+pub mod watch;
+
 /// Make sure the League of Legends Client is opened before running any of the methods.
 pub struct LeagueClientConnector {}
 
@@ -43,6 +68,12 @@ impl LeagueClientConnector {
         let mut path = PathBuf::from(Self::get_path()?);
         path.push("lockfile");
 
+        Self::parse_lockfile_at(&path)
+    }
+
+    /// Reads and parses a `lockfile` at a specific path. Shared with [`crate::watch`], which
+    /// needs to re-parse the `lockfile` at a known install directory whenever it changes.
+    pub(crate) fn parse_lockfile_at(path: &std::path::Path) -> Result<RiotLockFile> {
         let lockfile = path.to_str().ok_or(LeagueConnectorError::EmptyPath {})?;
 
         let contents = fs::read_to_string(lockfile).context(UnableToRead)?;
@@ -71,7 +102,7 @@ impl LeagueClientConnector {
     }
 
     /// Gets League of Legends Installation path. Useful to find the "lockfile" for example.
-    /// Works for Windows & Mac OSX
+    /// Works for Windows, Mac OSX & Linux (including the client running under Wine/Proton)
     ///
     /// # Example
     ///
@@ -83,27 +114,106 @@ impl LeagueClientConnector {
     /// assert!(path.len() > 0);
     /// ```
     pub fn get_path() -> Result<String> {
-        let raw_info: String = match OS {
-            "windows" => Self::get_raw_league_info_in_windows()?,
-            "macos" => Self::get_raw_league_info_in_macos()?,
+        let raw_info = Self::get_raw_league_info()?;
+
+        Self::parse_install_directory(&raw_info)
+    }
+
+    /// Builds a [`RiotLockFile`] straight from the running `LeagueClientUx` command line,
+    /// without touching the `lockfile` on disk. Useful in the window right after the client
+    /// starts, before it has finished writing the `lockfile`.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use league_client_connector::LeagueClientConnector;
+    ///
+    /// let lockfile = LeagueClientConnector::from_process_args().unwrap();
+    ///
+    /// assert!(lockfile.port > 0);
+    /// ```
+    pub fn from_process_args() -> Result<RiotLockFile> {
+        let raw_info = Self::get_raw_league_info()?;
+
+        let username = "riot".to_string();
+        let address = "127.0.0.1".to_string();
+        let protocol = "https".to_string();
+        let process = "LeagueClientUx".to_string();
+
+        let port = Self::capture(&raw_info, &APP_PORT_PATTERN)
+            .ok_or(LeagueConnectorError::MissingArg { name: "app-port" })?
+            .parse()
+            .context(NumberParse { name: "port" })?;
+
+        let pid = Self::capture(&raw_info, &APP_PID_PATTERN)
+            .ok_or(LeagueConnectorError::MissingArg { name: "app-pid" })?
+            .parse()
+            .context(NumberParse { name: "pid" })?;
+
+        let password = Self::capture(&raw_info, &AUTH_TOKEN_PATTERN)
+            .ok_or(LeagueConnectorError::NoAuthToken {})?;
+
+        let b64_auth = encode(format!("{}:{}", username, password).as_bytes());
+
+        Ok(RiotLockFile {
+            process,
+            pid,
+            port,
+            password,
+            protocol,
+            username,
+            address,
+            b64_auth,
+        })
+    }
+
+    /// Connects to the League Client, trying the `lockfile` first and transparently falling
+    /// back to scraping the running process' command line arguments when the `lockfile` hasn't
+    /// been written yet (or isn't readable).
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use league_client_connector::LeagueClientConnector;
+    ///
+    /// let lockfile = LeagueClientConnector::connect().unwrap();
+    ///
+    /// assert!(lockfile.port > 0);
+    /// ```
+    pub fn connect() -> Result<RiotLockFile> {
+        Self::parse_lockfile().or_else(|_| Self::from_process_args())
+    }
+
+    fn get_raw_league_info() -> Result<String> {
+        match OS {
+            "windows" => Self::get_raw_league_info_in_windows(),
+            "macos" => Self::get_raw_league_info_in_macos(),
+            "linux" => Self::get_raw_league_info_in_linux(),
             _ => unimplemented!(),
-        };
+        }
+    }
 
-        let pattern = Regex::new(r"--install-directory=(?P<dir>[[:alnum:][:space:]:\./\\]+)")
-            .context(RegexParse)?;
+    fn capture(raw_info: &str, pattern: &Regex) -> Option<String> {
+        pattern
+            .captures(raw_info)
+            .map(|caps| caps["value"].to_string())
+    }
 
-        let caps = pattern
-            .captures(&raw_info)
+    fn parse_install_directory(raw_info: &str) -> Result<String> {
+        let caps = INSTALL_DIRECTORY_PATTERN
+            .captures(raw_info)
             .ok_or(LeagueConnectorError::NoInstallationPath {})?;
 
-        let path = caps["dir"].to_string().trim().to_string();
+        // The capture is greedy over spaces (needed for "C:\Riot Games\League of Legends"-style
+        // paths), so it can run into the next `--flag`; cut it off there.
+        let dir = caps["dir"].split(" --").next().unwrap_or(&caps["dir"]);
 
-        Ok(path)
+        Ok(dir.trim().to_string())
     }
 
     fn get_raw_league_info_in_windows() -> Result<String> {
         let output_child = Command::new("WMIC")
-            .args(&[
+            .args([
                 "PROCESS",
                 "WHERE",
                 "name='LeagueClientUx.exe'",
@@ -120,27 +230,90 @@ impl LeagueClientConnector {
 
     fn get_raw_league_info_in_macos() -> Result<String> {
         let mut ps_output_child = Command::new("ps")
-            .args(&["x", "-o", "args"])
+            .args(["x", "-o", "args"])
             .stdout(Stdio::piped())
             .spawn()
             .context(GetRawPath)?;
 
-        let ps_output = if let Some(ps_output) = ps_output_child.stdout.take() {
-            ps_output
-        } else {
-            return Err(LeagueConnectorError::EmptyStdout {});
-        };
+        let ps_output = ps_output_child
+            .stdout
+            .take()
+            .ok_or(LeagueConnectorError::EmptyStdout {})?;
+
+        let res = Self::matching_lines(ps_output)?;
+        ps_output_child.wait().context(GetRawPath)?;
+
+        Ok(res)
+    }
+
+    /// Reads `ps` output line-by-line via [`process_lines`](Self::process_lines), joining only
+    /// the lines that mention `LeagueClientUx` back into a single string. Avoids shelling out to
+    /// `grep` and buffering the whole `ps` output up front.
+    fn matching_lines(output: impl Read) -> Result<String> {
+        let mut matches = String::new();
+
+        for line in Self::process_lines(output) {
+            matches.push_str(&line.context(GetRawPath)?);
+            matches.push('\n');
+        }
+
+        Ok(matches)
+    }
+
+    /// Streams `ps` output line-by-line, yielding only the lines that mention
+    /// `LeagueClientUx` instead of aborting at the first non-matching one. `Err` lines (e.g. a
+    /// genuine I/O or non-UTF8 read failure) are passed through rather than dropped, so callers
+    /// still see the real failure instead of it being misread as a missing process.
+    fn process_lines(output: impl Read) -> impl Iterator<Item = io::Result<String>> {
+        BufReader::new(output).lines().filter(|line| match line {
+            Ok(line) => line.contains("LeagueClientUx"),
+            Err(_) => true,
+        })
+    }
 
-        let output_child = Command::new("grep")
-            .args(&["LeagueClientUx"])
-            .stdin(ps_output)
+    /// Reads `/proc/*/cmdline` looking for the `LeagueClientUx` process, which is how native
+    /// Linux clients and the client running under Wine/Proton show up in the process table.
+    /// Falls back to `ps x -o args`, filtered through [`matching_lines`](Self::matching_lines),
+    /// when `/proc` can't be read or no entry in it mentions `LeagueClientUx`.
+    fn get_raw_league_info_in_linux() -> Result<String> {
+        if let Ok(entries) = fs::read_dir("/proc") {
+            for entry in entries {
+                let entry = match entry {
+                    Ok(entry) => entry,
+                    Err(_) => continue,
+                };
+
+                let cmdline = match fs::read_to_string(entry.path().join("cmdline")) {
+                    Ok(cmdline) => cmdline,
+                    Err(_) => continue,
+                };
+
+                // Arguments in /proc/*/cmdline are separated by null bytes.
+                let cmdline = cmdline.replace('\u{0}', " ");
+
+                if cmdline.contains("LeagueClientUx") {
+                    return Ok(cmdline);
+                }
+            }
+        }
+
+        Self::get_raw_league_info_in_linux_ps()
+    }
+
+    fn get_raw_league_info_in_linux_ps() -> Result<String> {
+        let mut ps_output_child = Command::new("ps")
+            .args(["x", "-o", "args"])
             .stdout(Stdio::piped())
             .spawn()
             .context(GetRawPath)?;
 
-        let output = output_child.wait_with_output().context(GetRawPath)?;
+        let ps_output = ps_output_child
+            .stdout
+            .take()
+            .ok_or(LeagueConnectorError::EmptyStdout {})?;
+
+        let res = Self::matching_lines(ps_output)?;
         ps_output_child.wait().context(GetRawPath)?;
-        let res = String::from_utf8(output.stdout).context(Utf8Parse)?;
 
         Ok(res)
     }
@@ -186,12 +359,30 @@ pub enum LeagueConnectorError {
     #[snafu(display("Unable to parse from utf8: {}", source))]
     Utf8Parse { source: std::string::FromUtf8Error },
 
-    #[snafu(display("Unable to parse Regex: {}", source))]
-    RegexParse { source: regex::Error },
-
     #[snafu(display("No installation path found for League"))]
     NoInstallationPath {},
 
+    #[snafu(display("No auth token found in the LeagueClientUx command line"))]
+    NoAuthToken {},
+
+    #[snafu(display("No `--{}` argument found in the LeagueClientUx command line", name))]
+    MissingArg { name: &'static str },
+
+    #[snafu(display("HTTP request to the LCU failed: {}", message))]
+    HttpRequest { message: String },
+
+    #[snafu(display("Unable to parse JSON: {}", source))]
+    JsonParse { source: serde_json::Error },
+
+    #[snafu(display("WebSocket error: {}", message))]
+    WebSocket { message: String },
+
+    #[snafu(display("Unable to watch the lockfile: {}", message))]
+    Watch { message: String },
+
+    #[snafu(display("The lockfile watcher has stopped"))]
+    WatchClosed {},
+
     #[snafu(display("Path is empty"))]
     EmptyPath {},
 
@@ -241,6 +432,59 @@ mod tests {
         assert_ne!(file1, file2);
     }
 
+    #[test]
+    fn parse_install_directory_linux_path() {
+        let raw_info = "/path/to/LeagueClientUx --install-directory=/home/user/Games/league-of-legends --other-flag=1";
+
+        let path = LeagueClientConnector::parse_install_directory(raw_info).unwrap();
+
+        assert_eq!(path, "/home/user/Games/league-of-legends".to_string());
+    }
+
+    #[test]
+    fn capture_app_port_and_auth_token() {
+        let raw_info =
+            "LeagueClientUx.exe --app-port=54321 --app-pid=9876 --remoting-auth-token=abc123";
+
+        let port = LeagueClientConnector::capture(raw_info, &APP_PORT_PATTERN).unwrap();
+        let token = LeagueClientConnector::capture(raw_info, &AUTH_TOKEN_PATTERN).unwrap();
+
+        assert_eq!(port, "54321".to_string());
+        assert_eq!(token, "abc123".to_string());
+    }
+
+    #[test]
+    fn capture_missing_value_returns_none() {
+        let raw_info = "LeagueClientUx.exe --app-port=54321";
+
+        let token = LeagueClientConnector::capture(raw_info, &AUTH_TOKEN_PATTERN);
+
+        assert!(token.is_none());
+    }
+
+    #[test]
+    fn process_lines_skips_non_matching_lines() {
+        let ps_output = b"root         1  init\nuser       234  /path/LeagueClientUx --app-port=1\nuser       235  some-other-process\n";
+
+        let lines: Vec<String> = LeagueClientConnector::process_lines(&ps_output[..])
+            .map(|line| line.unwrap())
+            .collect();
+
+        assert_eq!(lines, vec!["user       234  /path/LeagueClientUx --app-port=1".to_string()]);
+    }
+
+    #[test]
+    fn process_lines_propagates_read_errors() {
+        // Invalid UTF-8 makes `BufReader::lines()` yield an `Err` for that line; it must not be
+        // swallowed by the "does this mention LeagueClientUx" filter.
+        let ps_output = b"user       234  /path/LeagueClientUx --app-port=1\n\xff\xfe\n";
+
+        let lines: Vec<io::Result<String>> =
+            LeagueClientConnector::process_lines(&ps_output[..]).collect();
+
+        assert!(lines.iter().any(|line| line.is_err()));
+    }
+
     fn build_lockfile(port: u32, address: &str, b64_auth: &str) -> RiotLockFile {
         RiotLockFile {
             process: "1234".to_string(),
@@ -0,0 +1,232 @@
+//! Watches the League install directory's `lockfile` and broadcasts an updated
+//! [`RiotLockFile`] whenever the player closes and reopens the client, as described at the
+//! crate root. Gated behind the `watch` feature.
+
+use crate::{LeagueClientConnector, LeagueConnectorError, Result, RiotLockFile};
+use notify::{Event, EventKind, RecommendedWatcher, RecursiveMode, Watcher};
+use std::path::{Path, PathBuf};
+use std::sync::mpsc::{channel, Receiver, Sender};
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::Duration;
+
+/// How many times in total to attempt parsing a `lockfile` that was only partially written
+/// when the `create`/`modify` event fired (the first attempt plus up to 4 retries).
+const PARSE_RETRIES: u32 = 5;
+const PARSE_RETRY_DELAY: Duration = Duration::from_millis(20);
+
+/// An update pushed by a [`LockfileWatcher`] whenever the League Client (re)starts or closes.
+#[derive(Debug, Clone, PartialEq)]
+pub enum LockfileUpdate {
+    /// A fresh `lockfile` was parsed — the client (re)started with a new port/token.
+    Connected(RiotLockFile),
+    /// The `lockfile` was removed — the client closed.
+    Disconnected,
+}
+
+/// Watches the `lockfile` inside a League install directory and broadcasts a [`LockfileUpdate`]
+/// every time it's created, modified or removed. Every [`subscribe`](Self::subscribe)d consumer
+/// (and the default one backing [`recv`](Self::recv)) receives its own copy of each update, so
+/// long-running apps can have more than one independent reader.
+pub struct LockfileWatcher {
+    // Kept alive for as long as the watcher should keep running; dropping it stops the watch.
+    _watcher: RecommendedWatcher,
+    subscribers: Arc<Mutex<Vec<Sender<LockfileUpdate>>>>,
+    receiver: Receiver<LockfileUpdate>,
+}
+
+impl LockfileWatcher {
+    /// Starts watching the `lockfile` inside the install directory returned by
+    /// [`LeagueClientConnector::get_path`].
+    pub fn new() -> Result<Self> {
+        let install_dir = LeagueClientConnector::get_path()?;
+
+        Self::watch(install_dir)
+    }
+
+    /// Starts watching the `lockfile` inside a specific install directory.
+    pub fn watch(install_dir: impl Into<PathBuf>) -> Result<Self> {
+        let install_dir = install_dir.into();
+        let lockfile_path = install_dir.join("lockfile");
+
+        let (sender, receiver) = channel();
+        let subscribers = Arc::new(Mutex::new(vec![sender]));
+
+        let broadcast_subscribers = Arc::clone(&subscribers);
+        let mut watcher = notify::recommended_watcher(move |event: notify::Result<Event>| {
+            let event = match event {
+                Ok(event) => event,
+                Err(_) => return,
+            };
+
+            if !event.paths.iter().any(|path| path == &lockfile_path) {
+                return;
+            }
+
+            let update = match event.kind {
+                EventKind::Create(_) | EventKind::Modify(_) => {
+                    parse_lockfile_with_retry(&lockfile_path).map(LockfileUpdate::Connected)
+                }
+                EventKind::Remove(_) => Some(LockfileUpdate::Disconnected),
+                _ => None,
+            };
+
+            if let Some(update) = update {
+                // Drop any subscriber whose receiver has gone away, same as a plain mpsc send.
+                broadcast_subscribers
+                    .lock()
+                    .unwrap()
+                    .retain(|sender| sender.send(update.clone()).is_ok());
+            }
+        })
+        .map_err(watch_error)?;
+
+        watcher
+            .watch(&install_dir, RecursiveMode::NonRecursive)
+            .map_err(watch_error)?;
+
+        Ok(Self {
+            _watcher: watcher,
+            subscribers,
+            receiver,
+        })
+    }
+
+    /// Registers an additional, independent consumer of [`LockfileUpdate`]s: every subscriber
+    /// (including the default one backing [`recv`](Self::recv)) gets its own copy of each
+    /// update, so multiple readers can watch the same `lockfile` without stealing updates from
+    /// each other.
+    pub fn subscribe(&self) -> Receiver<LockfileUpdate> {
+        let (sender, receiver) = channel();
+        self.subscribers.lock().unwrap().push(sender);
+
+        receiver
+    }
+
+    /// Blocks the calling thread until the next [`LockfileUpdate`].
+    pub fn recv(&self) -> Result<LockfileUpdate> {
+        self.receiver
+            .recv()
+            .map_err(|_| LeagueConnectorError::WatchClosed {})
+    }
+
+    /// Invokes `callback` for every subsequent [`LockfileUpdate`], blocking the calling thread.
+    pub fn watch_blocking(&self, mut callback: impl FnMut(LockfileUpdate)) -> Result<()> {
+        loop {
+            callback(self.recv()?);
+        }
+    }
+
+    /// Turns this watcher into an async [`Stream`](futures_util::Stream) of [`LockfileUpdate`]s,
+    /// consuming the default subscriber backing [`recv`](Self::recv).
+    #[cfg(feature = "watch-stream")]
+    pub fn into_stream(self) -> impl futures_util::Stream<Item = LockfileUpdate> {
+        futures_util::stream::unfold(
+            (self._watcher, self.receiver),
+            |(watcher, receiver)| async move {
+                let (update, receiver) =
+                    tokio::task::spawn_blocking(move || receiver.recv().ok().map(|u| (u, receiver)))
+                        .await
+                        .ok()
+                        .flatten()?;
+
+                Some((update, (watcher, receiver)))
+            },
+        )
+    }
+}
+
+/// Re-parses the `lockfile` at `path`, retrying a few times if it's caught mid-write (a race
+/// where the file exists but the client hasn't finished flushing it yet, surfacing as a
+/// `NumberParse` error).
+fn parse_lockfile_with_retry(path: &Path) -> Option<RiotLockFile> {
+    for attempt in 0..PARSE_RETRIES {
+        match LeagueClientConnector::parse_lockfile_at(path) {
+            Ok(lockfile) => return Some(lockfile),
+            Err(LeagueConnectorError::NumberParse { .. }) if attempt + 1 < PARSE_RETRIES => {
+                thread::sleep(PARSE_RETRY_DELAY);
+            }
+            Err(_) => return None,
+        }
+    }
+
+    None
+}
+
+fn watch_error(source: notify::Error) -> LeagueConnectorError {
+    LeagueConnectorError::Watch {
+        message: source.to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+
+    #[test]
+    fn parse_lockfile_with_retry_reads_a_well_formed_file() {
+        let dir = std::env::temp_dir().join("lcu_watch_test_well_formed");
+        fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("lockfile");
+        fs::write(&path, "LeagueClientUx:1234:54321:some_password:https").unwrap();
+
+        let lockfile = parse_lockfile_with_retry(&path).unwrap();
+
+        assert_eq!(lockfile.process, "LeagueClientUx");
+        assert_eq!(lockfile.port, 54321);
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn parse_lockfile_with_retry_recovers_from_a_partial_write() {
+        let dir = std::env::temp_dir().join("lcu_watch_test_partial_write");
+        fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("lockfile");
+
+        // Simulate the client still flushing the file: the port field isn't a number yet.
+        fs::write(&path, "LeagueClientUx:1234:54XXX:some_password:https").unwrap();
+
+        let path_clone = path.clone();
+        let writer = thread::spawn(move || {
+            thread::sleep(PARSE_RETRY_DELAY * 2);
+            fs::write(&path_clone, "LeagueClientUx:1234:54321:some_password:https").unwrap();
+        });
+
+        let lockfile = parse_lockfile_with_retry(&path).unwrap();
+        writer.join().unwrap();
+
+        assert_eq!(lockfile.port, 54321);
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn parse_lockfile_with_retry_gives_up_on_a_missing_file() {
+        let path = std::env::temp_dir().join("lcu_watch_test_missing/lockfile");
+
+        assert!(parse_lockfile_with_retry(&path).is_none());
+    }
+
+    #[test]
+    fn subscribers_each_receive_their_own_copy_of_an_update() {
+        let dir = std::env::temp_dir().join("lcu_watch_test_subscribers");
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+
+        let watcher = LockfileWatcher::watch(&dir).unwrap();
+        let other = watcher.subscribe();
+
+        fs::write(dir.join("lockfile"), "LeagueClientUx:1234:54321:some_password:https").unwrap();
+
+        let timeout = Duration::from_secs(5);
+        let first = watcher.receiver.recv_timeout(timeout).unwrap();
+        let second = other.recv_timeout(timeout).unwrap();
+
+        assert_eq!(first, second);
+        assert!(matches!(first, LockfileUpdate::Connected(_)));
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}